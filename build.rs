@@ -6,7 +6,7 @@ use anyhow::{
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
     fmt::Debug,
     fs::{self, File},
@@ -15,6 +15,8 @@ use std::{
     process::Command,
 };
 
+use elf::{abi, endian::AnyEndian, ElfBytes};
+
 type Toml = toml::value::Value;
 
 const UTF8_PATH: &'static str = "path should be valid UTF-8 string.";
@@ -30,50 +32,80 @@ fn check_os( table: &toml::Table ) -> Result<bool> {
 }
 
 fn match_os( name: &str ) -> bool {
+    let target_os = env::var( "CARGO_CFG_TARGET_OS" ).unwrap_or_default();
+    let target_family = env::var( "CARGO_CFG_TARGET_FAMILY" ).unwrap_or_default();
+
     match name {
-        "android"   => if cfg!( target_os = "android"   ) {true} else {false},
-        "dragonfly" => if cfg!( target_os = "dragonfly" ) {true} else {false},
-        "freebsd"   => if cfg!( target_os = "freebsd"   ) {true} else {false},
-        "ios"       => if cfg!( target_os = "ios"       ) {true} else {false},
-        "linux"     => if cfg!( target_os = "linux"     ) {true} else {false},
-        "macos"     => if cfg!( target_os = "macos"     ) {true} else {false},
-        "netbsd"    => if cfg!( target_os = "netbsd"    ) {true} else {false},
-        "openbsd"   => if cfg!( target_os = "openbsd"   ) {true} else {false},
-        "windows"   => if cfg!( target_os = "windows"   ) {true} else {false},
-        "unix"      => if cfg!(              unix       ) {true} else {false},
+        "android"   => target_os == "android",
+        "dragonfly" => target_os == "dragonfly",
+        "freebsd"   => target_os == "freebsd",
+        "ios"       => target_os == "ios",
+        "linux"     => target_os == "linux",
+        "macos"     => target_os == "macos",
+        "netbsd"    => target_os == "netbsd",
+        "openbsd"   => target_os == "openbsd",
+        "windows"   => target_os == "windows",
+        "unix"      => target_family.split(',').any( |family| family == "unix" ),
         _           => false,
     }
 }
 
+fn configure_cross_pkg_config() {
+    env::set_var( "PKG_CONFIG_ALLOW_CROSS", "1" );
+
+    let ( Ok( target ), Ok( host )) = ( env::var("TARGET"), env::var("HOST") ) else { return };
+    if target == host {
+        return;
+    }
+
+    let triple = target.replace( '-', "_" );
+    for base in [ "PKG_CONFIG_SYSROOT_DIR", "PKG_CONFIG_PATH", "PKG_CONFIG_LIBDIR" ] {
+        if let Ok( value ) = env::var( base ) {
+            let scoped = format!( "{}_{}", base, triple );
+            if env::var( &scoped ).is_err() {
+                env::set_var( &scoped, value );
+            }
+        }
+    }
+}
+
 #[derive( Debug )]
 pub struct LibInfo {
-    link_paths    : RefCell<Vec<String>>,
-    include_paths : RefCell<Vec<String>>,
-    headers       : RefCell<Vec<String>>,
-    specs         : HashMap<String,Toml>,
+    link_paths       : RefCell<Vec<String>>,
+    include_paths    : RefCell<Vec<String>>,
+    headers          : RefCell<Vec<String>>,
+    probed_binaries  : RefCell<HashSet<PathBuf>>,
+    specs            : HashMap<String,Toml>,
 }
 
 impl LibInfo {
     fn new( specs: HashMap<String,Toml> ) -> Self {
         LibInfo {
-            link_paths    : RefCell::default(),
-            include_paths : RefCell::default(),
-            headers       : RefCell::default(),
-            specs         ,
+            link_paths      : RefCell::default(),
+            include_paths   : RefCell::default(),
+            headers         : RefCell::default(),
+            probed_binaries : RefCell::default(),
+            specs           ,
         }
     }
 
     fn probe( &self, pkg_name: &str, scan_incdir: bool ) -> Result<()> {
         let probed_ex = self
             .probe_via_pkgconf( pkg_name, scan_incdir )
-            .or_else( |_| self.probe_via_search( pkg_name, scan_incdir ))?;
+            .or_else( |_| self.probe_via_search( pkg_name, scan_incdir ))
+            .or_else( |_| self.probe_via_config( pkg_name, scan_incdir ))
+            .or_else( |_| self.probe_via_binary( pkg_name, scan_incdir ))?;
 
         if scan_incdir {
             self.include_paths.borrow_mut().push( self.get_includedir( &probed_ex )? );
         }
 
+        self.register_headers_and_dependencies( pkg_name, &probed_ex, scan_incdir )
+    }
+
+    fn register_headers_and_dependencies( &self, pkg_name: &str, probed_ex: &ProbedEx, scan_incdir: bool ) -> Result<()> {
         if let Some( spec ) = self.specs.get( pkg_name ) {
-            let include_dir = self.get_includedir( &probed_ex )?;
+            let include_dir = self.get_includedir( probed_ex )?;
 
             if let Some( table ) = spec.as_table() {
                 if !scan_incdir {
@@ -137,6 +169,7 @@ impl LibInfo {
     fn probe_via_pkgconf( &self, pkg_name: &str, scan_incdir: bool ) -> Result<ProbedEx> {
         env::set_var( "PKG_CONFIG_ALLOW_SYSTEM_CFLAGS", "1" );
         env::set_var( "PKG_CONFIG_ALLOW_SYSTEM_LIBS", "1" );
+        configure_cross_pkg_config();
 
         let mut cfg = pkg_config::Config::new();
         cfg.cargo_metadata( true );
@@ -154,6 +187,24 @@ impl LibInfo {
                         pc_file_names.push( pc.as_str().expect( ".pc file name should be str." ));
                     });
             }
+
+            if let Some( version ) = table.get("version").and_then( |v| v.as_table() ) {
+                let atleast = version.get("atleast").and_then( |v| v.as_str() );
+                let max = version.get("max").and_then( |v| v.as_str() );
+                if let Some( exact ) = version.get("exact").and_then( |v| v.as_str() ) {
+                    cfg.exactly_version( exact );
+                } else if let ( Some( atleast ), Some( max )) = ( atleast, max ) {
+                    cfg.range_version( atleast..max );
+                } else if let Some( atleast ) = atleast {
+                    cfg.atleast_version( atleast );
+                } else if let Some( max ) = max {
+                    cfg.range_version( "0"..max );
+                }
+            }
+
+            if resolve_kind( table ).as_deref() == Some( "static" ) {
+                cfg.statik( true );
+            }
         }
 
         let mut names = pc_file_names.into_iter();
@@ -192,6 +243,12 @@ impl LibInfo {
             .unwrap()
             .as_table()
         {
+            for root in search_roots( table ) {
+                if let Ok( probed ) = self.try_prefix( pkg_name, table, &root, scan_incdir ) {
+                    return Ok( probed );
+                }
+            }
+
             if let Some( executable_names ) = table.get( "exe" ).and_then( |exe| exe.as_array() ) {
                 for name in executable_names {
                     let name = name.as_str().expect("exe names should be str.");
@@ -229,12 +286,15 @@ impl LibInfo {
                                 })
                                 .expect("include_path");
 
+                            enforce_version_from_tool( pkg_name, table, name )?;
+
                             if !scan_incdir {
+                                let kind = resolve_kind( table );
                                 self.link_paths.borrow_mut().push( prefix.join("lib").to_str().expect( UTF8_PATH ).to_owned() );
                                 println!( "cargo:rustc-link-search=native={}/lib", prefix.to_str().expect( UTF8_PATH ));
-                                emit_cargo_meta_for_libs( &prefix, table.get( "libs" ).expect( "metadata should contain libs" ))?;
+                                emit_cargo_meta_for_libs( &prefix, table.get( "libs" ).expect( "metadata should contain libs" ), kind.as_deref() )?;
                                 if let Some( libs ) = table.get( "libs-private" ) {
-                                    emit_cargo_meta_for_libs( &prefix, libs )?;
+                                    emit_cargo_meta_for_libs( &prefix, libs, kind.as_deref() )?;
                                 }
                             }
                             return Ok( ProbedEx::IncDir( guess_include ));
@@ -251,6 +311,171 @@ impl LibInfo {
         }
     }
 
+    fn probe_via_config( &self, pkg_name: &str, scan_incdir: bool ) -> Result<ProbedEx> {
+        let table = self.specs
+            .get( pkg_name )
+            .and_then( |spec| spec.as_table() )
+            .context( "failed to locate config script." )?;
+
+        let config = table.get( "config" ).context( "no config script declared." )?;
+
+        let (script, args): (&str, Vec<&str>) = match config {
+            Toml::String( script ) => ( script.as_str(), vec![ "--cflags", "--libs" ] ),
+            Toml::Array( items ) => {
+                let mut names = items.iter();
+                let script = names.next()
+                    .and_then( |name| name.as_str() )
+                    .context( "config script name should be str." )?;
+                let args: Vec<&str> = names
+                    .map( |arg| arg.as_str().expect( "config argument should be str." ))
+                    .collect();
+                ( script, if args.is_empty() { vec![ "--cflags", "--libs" ] } else { args })
+            },
+            _ => return Err( anyhow!( "config should be a str or an array." )),
+        };
+
+        let output = Command::new( script ).args( &args ).output()
+            .with_context( || format!( "failed to run `{} {}`.", script, args.join(" ") ))?;
+
+        enforce_version_from_tool( pkg_name, table, script )?;
+
+        let mut include_dir = None;
+        for token in std::str::from_utf8( &output.stdout )?.split_whitespace() {
+            if let Some( path ) = token.strip_prefix( "-I" ) {
+                if include_dir.is_none() {
+                    include_dir = Some( path.to_owned() );
+                }
+                if !scan_incdir {
+                    self.include_paths.borrow_mut().push( path.to_owned() );
+                }
+            } else if let Some( path ) = token.strip_prefix( "-L" ) {
+                if !scan_incdir {
+                    self.link_paths.borrow_mut().push( path.to_owned() );
+                    println!( "cargo:rustc-link-search=native={}", path );
+                }
+            } else if let Some( lib_name ) = token.strip_prefix( "-l" ) {
+                if !scan_incdir {
+                    println!( "cargo:rustc-link-lib={}", lib_name );
+                }
+            }
+        }
+
+        Ok( ProbedEx::IncDir( include_dir.context( "config script produced no -I flag." )? ))
+    }
+
+    fn try_prefix( &self, pkg_name: &str, table: &toml::Table, prefix: &Path, scan_incdir: bool ) -> Result<ProbedEx> {
+        let include_base = prefix.join("include");
+        let lib_base = prefix.join("lib");
+
+        let guess_include = table
+            .get("includedir")
+            .and_then( |includedirs| includedirs.as_array() )
+            .and_then( |dirs| dirs
+                .iter()
+                .map( |dir| dir.as_str().expect( "include dir should be str." ))
+                .map( |dir| include_base.join( dir ))
+                .find( |dir| dir.exists() ))
+            .unwrap_or_else( || include_base.clone() );
+
+        if !guess_include.exists() || !lib_base.exists() {
+            return Err( anyhow!( "{:?} is not a usable prefix for {:?}.", prefix, table ));
+        }
+
+        enforce_version_from_prefix( pkg_name, table, prefix )?;
+
+        if !scan_incdir {
+            let kind = resolve_kind( table );
+            self.link_paths.borrow_mut().push( lib_base.to_str().expect( UTF8_PATH ).to_owned() );
+            println!( "cargo:rustc-link-search=native={}", lib_base.to_str().expect( UTF8_PATH ));
+            emit_cargo_meta_for_libs( prefix, table.get( "libs" ).expect( "metadata should contain libs" ), kind.as_deref() )?;
+            if let Some( libs ) = table.get( "libs-private" ) {
+                emit_cargo_meta_for_libs( prefix, libs, kind.as_deref() )?;
+            }
+        }
+
+        Ok( ProbedEx::IncDir( guess_include.to_str().expect( UTF8_PATH ).to_owned() ))
+    }
+
+    fn probe_via_binary( &self, pkg_name: &str, scan_incdir: bool ) -> Result<ProbedEx> {
+        let table = self.specs
+            .get( pkg_name )
+            .and_then( |spec| spec.as_table() )
+            .context( "failed to locate spec for binary probe." )?;
+
+        let prefix = locate_prefix_via_exe( table )
+            .context( "failed to locate a prefix via `exe` for binary probe." )?;
+        let lib_path = prefix.join("lib");
+
+        let libs = table.get("libs").context( "metadata should contain libs." )?;
+        let lib_file = find_existing_lib( &lib_path, libs )
+            .context( "none of the declared libs were found for binary introspection." )?;
+
+        if !scan_incdir {
+            let lib_path_str = lib_path.to_str().expect( UTF8_PATH ).to_owned();
+            self.link_paths.borrow_mut().push( lib_path_str.clone() );
+            println!( "cargo:rustc-link-search=native={}", lib_path_str );
+
+            let lib_name = lib_file.file_name().and_then( |name| name.to_str() ).expect( UTF8_PATH );
+            println!( "cargo:rustc-link-lib={}", get_link_name( lib_name ));
+
+            self.collect_binary_closure( &lib_file, &[ lib_path_str ])?;
+        }
+
+        Ok( ProbedEx::Binary( lib_file ))
+    }
+
+    fn collect_binary_closure( &self, binary_path: &Path, extra_search_dirs: &[String] ) -> Result<()> {
+        if !self.probed_binaries.borrow_mut().insert( binary_path.to_owned() ) {
+            return Ok(());
+        }
+
+        let (needed, mut search_dirs) = needed_and_rpaths( binary_path )?;
+        search_dirs.extend( extra_search_dirs.iter().cloned() );
+
+        for dir in &search_dirs {
+            if self.link_paths.borrow().iter().any( |known| known == dir ) {
+                continue;
+            }
+            self.link_paths.borrow_mut().push( dir.clone() );
+            println!( "cargo:rustc-link-search=native={}", dir );
+        }
+
+        for needed_lib in &needed {
+            let found = search_dirs
+                .iter()
+                .map( |dir| Path::new( dir ).join( needed_lib ))
+                .find( |path| path.exists() );
+
+            if let Some( found ) = found {
+                println!( "cargo:rustc-link-lib={}", get_soname_link_name( needed_lib ));
+                self.collect_binary_closure( &found, &search_dirs )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build_and_probe_from_source( &self, pkg_name: &str ) -> Result<()> {
+        let table = self.specs
+            .get( pkg_name )
+            .and_then( |spec| spec.as_table() )
+            .context( "no spec table for source build." )?;
+        let source = table.get("source")
+            .and_then( |source| source.as_table() )
+            .context( "spec should declare a `source` table." )?;
+
+        let out_dir = PathBuf::from( env::var("OUT_DIR").expect( "$OUT_DIR should exist." ));
+        let checkout_dir = out_dir.join("clib-src").join( pkg_name );
+        let install_dir = out_dir.join("clib-install").join( pkg_name );
+
+        fetch_source( source, &checkout_dir )?;
+        run_build_commands( source, &checkout_dir, &install_dir )?;
+        copy_build_artifacts( source, &checkout_dir, &install_dir )?;
+
+        let probed_ex = self.try_prefix( pkg_name, table, &install_dir, false )?;
+        self.register_headers_and_dependencies( pkg_name, &probed_ex, false )
+    }
+
     fn get_includedir( &self, probe_ex: &ProbedEx ) -> Result<String> {
         match probe_ex {
             ProbedEx::PcName( pc_name ) => {
@@ -268,12 +493,237 @@ impl LibInfo {
                 assert!( path.exists() );
                 Ok( format!( "{}", path.display() ))
             },
+            ProbedEx::Binary( lib_file ) => {
+                let prefix = lib_file.parent()
+                    .and_then( |lib_dir| lib_dir.parent() )
+                    .context( "failed to infer a prefix from the probed binary's path." )?;
+                Ok( format!( "{}", prefix.join("include").display() ))
+            },
         }
     }
 }
 
-fn emit_cargo_meta_for_libs( prefix: &Path, value: &Toml ) -> Result<()> {
-    let lib_path = prefix.join("lib");
+fn search_roots( table: &toml::Table ) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = table
+        .get("search-paths")
+        .and_then( |paths| paths.as_array() )
+        .map( |paths| paths
+            .iter()
+            .map( |path| PathBuf::from( path.as_str().expect( "search-paths entry should be str." )))
+            .collect() )
+        .unwrap_or_default();
+
+    if let Ok( clib_path ) = env::var("CLIB_PATH") {
+        roots.extend( env::split_paths( &clib_path ));
+    }
+
+    roots
+}
+
+fn fetch_source( source: &toml::Table, checkout_dir: &Path ) -> Result<()> {
+    if checkout_dir.exists() {
+        return Ok(());
+    }
+
+    let checkout_dir_str = checkout_dir.to_str().context( UTF8_PATH )?;
+
+    if let Some( git_url ) = source.get("git").and_then( |v| v.as_str() ) {
+        fs::create_dir_all( checkout_dir.parent().context( "checkout dir should have a parent." )? )?;
+
+        let status = Command::new("git").args([ "clone", git_url, checkout_dir_str ]).status()
+            .context( "failed to run `git clone`." )?;
+        if !status.success() {
+            return Err( anyhow!( "`git clone {}` exited with {}.", git_url, status ));
+        }
+
+        if let Some( rev ) = source.get("tag").or_else( || source.get("rev") ).and_then( |v| v.as_str() ) {
+            let status = Command::new("git").args([ "-C", checkout_dir_str, "checkout", rev ]).status()
+                .context( "failed to run `git checkout`." )?;
+            if !status.success() {
+                return Err( anyhow!( "`git checkout {}` exited with {}.", rev, status ));
+            }
+        }
+    } else if let Some( tarball_url ) = source.get("tarball").and_then( |v| v.as_str() ) {
+        fs::create_dir_all( checkout_dir )?;
+        let archive = checkout_dir.with_extension("archive");
+        let archive_str = archive.to_str().context( UTF8_PATH )?;
+
+        let status = Command::new("curl").args([ "-fsSL", "-o", archive_str, tarball_url ]).status()
+            .context( "failed to download source tarball." )?;
+        if !status.success() {
+            return Err( anyhow!( "downloading {} exited with {}.", tarball_url, status ));
+        }
+
+        let status = Command::new("tar")
+            .args([ "xf", archive_str, "--strip-components=1", "-C", checkout_dir_str ])
+            .status()
+            .context( "failed to extract source tarball." )?;
+        if !status.success() {
+            return Err( anyhow!( "extracting {} exited with {}.", tarball_url, status ));
+        }
+    } else {
+        return Err( anyhow!( "`source` table should declare a `git` or `tarball` url." ));
+    }
+
+    Ok(())
+}
+
+fn run_build_commands( source: &toml::Table, checkout_dir: &Path, install_dir: &Path ) -> Result<()> {
+    fs::create_dir_all( install_dir )?;
+    let prefix = install_dir.to_str().context( UTF8_PATH )?;
+
+    let build = source.get("build").context( "`source` table should declare a `build` command." )?;
+    let commands: Vec<String> = match build {
+        Toml::String( command ) => vec![ command.replace( "{prefix}", prefix ) ],
+        Toml::Array( commands ) => commands
+            .iter()
+            .map( |command| command.as_str().expect( "build command should be str." ).replace( "{prefix}", prefix ))
+            .collect(),
+        _ => return Err( anyhow!( "`build` should be a str or an array of str." )),
+    };
+
+    for command in commands {
+        let status = Command::new( if cfg!(unix) { "sh" } else { "cmd" })
+            .arg( if cfg!(unix) { "-c" } else { "/C" })
+            .arg( &command )
+            .current_dir( checkout_dir )
+            .status()
+            .with_context( || format!( "failed to run build command `{}`.", command ))?;
+        if !status.success() {
+            return Err( anyhow!( "build command `{}` exited with {}.", command, status ));
+        }
+    }
+
+    Ok(())
+}
+
+fn copy_build_artifacts( source: &toml::Table, checkout_dir: &Path, install_dir: &Path ) -> Result<()> {
+    let Some( copies ) = source.get("copy").and_then( |copy| copy.as_table() ) else { return Ok(()) };
+
+    for (from, to) in copies {
+        let to = to.as_str().context( "`copy` destination should be str." )?;
+        let src = checkout_dir.join( from );
+        let dst = install_dir.join( to );
+
+        if let Some( parent ) = dst.parent() {
+            fs::create_dir_all( parent )?;
+        }
+
+        fs::copy( &src, &dst ).with_context( || format!( "failed to copy {:?} to {:?}.", src, dst ))?;
+        fs::set_permissions( &dst, fs::metadata( &src )?.permissions() )
+            .with_context( || format!( "failed to set permissions on {:?}.", dst ))?;
+    }
+
+    Ok(())
+}
+
+fn locate_prefix_via_exe( table: &toml::Table ) -> Option<PathBuf> {
+    let executable_names = table.get("exe")?.as_array()?;
+
+    for name in executable_names {
+        let name = name.as_str().expect("exe names should be str.");
+        let output = Command::new( if cfg!(unix) { "which" } else { "where" }).arg( name ).output().ok()?;
+        let s = output.stdout.as_slice();
+        if s.is_empty() {
+            continue;
+        }
+        let cmd_path = Path::new( std::str::from_utf8( s ).expect( UTF8_PATH ).trim_end() );
+        let parent = cmd_path.parent()?;
+        if parent.file_name() != Some( std::ffi::OsStr::new("bin") ) {
+            continue;
+        }
+        return parent.parent().map( Path::to_owned );
+    }
+    None
+}
+
+fn find_existing_lib( lib_path: &Path, value: &Toml ) -> Option<PathBuf> {
+    let candidate_lists: Vec<&Vec<Toml>> = if let Some( table ) = value.as_table() {
+        table.values().filter_map( |names| names.as_array() ).collect()
+    } else if let Some( names ) = value.as_array() {
+        vec![ names ]
+    } else {
+        return None;
+    };
+
+    candidate_lists
+        .into_iter()
+        .flatten()
+        .filter_map( |name| name.as_str() )
+        .map( |name| lib_path.join( name ))
+        .find( |path| path.exists() )
+}
+
+fn needed_and_rpaths( binary_path: &Path ) -> Result<(Vec<String>, Vec<String>)> {
+    let data = fs::read( binary_path )
+        .with_context( || format!( "failed to read {:?}.", binary_path ))?;
+    let elf_file = ElfBytes::<AnyEndian>::minimal_parse( &data )
+        .map_err( |err| anyhow!( "failed to parse ELF {:?}: {err}", binary_path ))?;
+    let common = elf_file.find_common_data()
+        .map_err( |err| anyhow!( "failed to read ELF sections of {:?}: {err}", binary_path ))?;
+    let dynamic = common.dynamic.context( "no PT_DYNAMIC segment." )?;
+    let dynstrs = common.dynsyms_strs.context( "no dynamic string table." )?;
+
+    let mut needed = Vec::new();
+    let mut rpaths = Vec::new();
+    let mut runpaths = Vec::new();
+
+    for entry in dynamic.iter() {
+        match entry.d_tag {
+            abi::DT_NEEDED  => needed.push( dynstrs.get( entry.d_val() as usize )?.to_owned() ),
+            abi::DT_RPATH   => rpaths.extend( dynstrs.get( entry.d_val() as usize )?.split(':').map( str::to_owned )),
+            abi::DT_RUNPATH => runpaths.extend( dynstrs.get( entry.d_val() as usize )?.split(':').map( str::to_owned )),
+            _ => {},
+        }
+    }
+
+    // DT_RUNPATH supersedes DT_RPATH when both are present.
+    let search_dirs = if runpaths.is_empty() { rpaths } else { runpaths };
+    let origin = binary_path.parent().context( "binary should have a parent directory." )?;
+    let origin = origin.to_str().expect( UTF8_PATH );
+    // $ORIGIN refers to the directory containing the binary that carries the RPATH/RUNPATH entry.
+    let search_dirs = search_dirs.into_iter().map( |dir| dir.replace( "$ORIGIN", origin )).collect();
+
+    Ok( (needed, search_dirs) )
+}
+
+fn resolve_kind( table: &toml::Table ) -> Option<String> {
+    match table.get("kind")? {
+        Toml::String( kind ) => Some( kind.clone() ),
+        Toml::Table( by_os ) => by_os
+            .iter()
+            .find( |(os_name, _)| *os_name != "default" && match_os( os_name ))
+            .or_else( || by_os.iter().find( |(os_name, _)| *os_name == "default" ))
+            .and_then( |(_, kind)| kind.as_str() )
+            .map( |kind| kind.to_owned() ),
+        _ => None,
+    }
+}
+
+// Frameworks live as `Name.framework` bundles under a `Frameworks` directory, not as
+// `lib*.so`/`lib*.a` files under `lib`, so they need their own search dir and existence check.
+fn locate_lib( prefix: &Path, lib_name: &str, kind: Option<&str> ) -> Option<(PathBuf, String)> {
+    if kind == Some("framework") {
+        let link_name = get_link_name( lib_name ).to_owned();
+        let frameworks_dir = prefix.join("Frameworks");
+        if frameworks_dir.join( format!( "{}.framework", link_name )).exists() {
+            Some( (frameworks_dir, link_name) )
+        } else {
+            None
+        }
+    } else {
+        let lib_path = prefix.join("lib");
+        if lib_path.join( lib_name ).exists() {
+            Some( (lib_path, get_link_name( lib_name ).to_owned()) )
+        } else {
+            None
+        }
+    }
+}
+
+fn emit_cargo_meta_for_libs( prefix: &Path, value: &Toml, kind: Option<&str> ) -> Result<()> {
+    let kind_prefix = kind.map( |kind| format!( "{}=", kind )).unwrap_or_default();
+    let is_framework = kind == Some("framework");
 
     if let Some( table ) = value.as_table() {
         'values:
@@ -281,18 +731,24 @@ fn emit_cargo_meta_for_libs( prefix: &Path, value: &Toml ) -> Result<()> {
             let lib_names = value.as_array().expect("names of libs should be an array.");
             for lib_name in lib_names {
                 let lib_name = lib_name.as_str().expect( "lib name should be str." );
-                if lib_path.join( lib_name ).exists() {
-                    println!( "cargo:rustc-link-lib={}", get_link_name( lib_name ));
+                if let Some( (search_dir, link_name) ) = locate_lib( prefix, lib_name, kind ) {
+                    if is_framework {
+                        println!( "cargo:rustc-link-search=framework={}", search_dir.to_str().expect( UTF8_PATH ));
+                    }
+                    println!( "cargo:rustc-link-lib={}{}", kind_prefix, link_name );
                     continue 'values;
                 }
             }
-            return Err( anyhow!( "lib should be found in {:?} directory.", lib_path ));
+            return Err( anyhow!( "lib should be found for prefix {:?}.", prefix ));
         }
     } else if let Some( lib_names ) = value.as_array() {
         for lib_name in lib_names {
             let lib_name = lib_name.as_str().expect("lib name should be str.");
-            if lib_path.join( lib_name ).exists() {
-                println!( "cargo:rustc-link-lib={}", get_link_name( lib_name ));
+            if let Some( (search_dir, link_name) ) = locate_lib( prefix, lib_name, kind ) {
+                if is_framework {
+                    println!( "cargo:rustc-link-search=framework={}", search_dir.to_str().expect( UTF8_PATH ));
+                }
+                println!( "cargo:rustc-link-lib={}{}", kind_prefix, link_name );
             } else {
                 return Err( anyhow!( "failed to locate {}", lib_name ));
             }
@@ -301,6 +757,86 @@ fn emit_cargo_meta_for_libs( prefix: &Path, value: &Toml ) -> Result<()> {
     Ok(())
 }
 
+fn parse_version_triple( text: &str ) -> Option<(u32,u32,u32)> {
+    let tokens: Vec<&str> = text
+        .split( |c: char| c.is_whitespace() || c == '.' )
+        .filter( |token| !token.is_empty() )
+        .collect();
+
+    tokens
+        .windows(3)
+        .find_map( |window| match ( window[0].parse(), window[1].parse(), window[2].parse() ) {
+            ( Ok( major ), Ok( minor ), Ok( patch )) => Some( ( major, minor, patch )),
+            _ => None,
+        })
+        .or_else( || tokens
+            .windows(2)
+            .find_map( |window| match ( window[0].parse(), window[1].parse() ) {
+                ( Ok( major ), Ok( minor )) => Some( ( major, minor, 0 )),
+                _ => None,
+            }))
+        .or_else( || tokens
+            .iter()
+            .find_map( |token| token.parse().ok().map( |major| ( major, 0, 0 ))))
+}
+
+fn check_version_bounds( pkg_name: &str, found: (u32,u32,u32), version: &toml::Table ) -> Result<()> {
+    if let Some( exact ) = version.get("exact").and_then( |v| v.as_str() ) {
+        let exact = parse_version_triple( exact ).context( "invalid `exact` version in spec." )?;
+        if found != exact {
+            return Err( anyhow!( "{} version {:?} does not match required exact version {:?}.", pkg_name, found, exact ));
+        }
+    }
+    if let Some( atleast ) = version.get("atleast").and_then( |v| v.as_str() ) {
+        let atleast = parse_version_triple( atleast ).context( "invalid `atleast` version in spec." )?;
+        if found < atleast {
+            return Err( anyhow!( "{} version {:?} is older than the required minimum {:?}.", pkg_name, found, atleast ));
+        }
+    }
+    if let Some( max ) = version.get("max").and_then( |v| v.as_str() ) {
+        let max = parse_version_triple( max ).context( "invalid `max` version in spec." )?;
+        if found > max {
+            return Err( anyhow!( "{} version {:?} is newer than the allowed maximum {:?}.", pkg_name, found, max ));
+        }
+    }
+    Ok(())
+}
+
+fn enforce_version_from_tool( pkg_name: &str, table: &toml::Table, tool: &str ) -> Result<()> {
+    let Some( version ) = table.get("version").and_then( |v| v.as_table() ) else { return Ok(()) };
+    let flag = version.get("flag").and_then( |f| f.as_str() ).unwrap_or( "--version" );
+
+    let output = Command::new( tool ).arg( flag ).output()
+        .with_context( || format!( "failed to run `{} {}`.", tool, flag ))?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy( &output.stdout ),
+        String::from_utf8_lossy( &output.stderr ),
+    );
+    let found = parse_version_triple( &text )
+        .with_context( || format!( "failed to parse a version out of `{} {}` output.", tool, flag ))?;
+
+    check_version_bounds( pkg_name, found, version )
+}
+
+fn enforce_version_from_prefix( pkg_name: &str, table: &toml::Table, prefix: &Path ) -> Result<()> {
+    if table.get("version").is_none() {
+        return Ok(());
+    }
+    let executable_names = table.get("exe")
+        .and_then( |exe| exe.as_array() )
+        .context( "spec declares a `version` bound but no `exe` to check it against." )?;
+    let bin_path = prefix.join("bin");
+    let tool = executable_names
+        .iter()
+        .filter_map( |name| name.as_str() )
+        .map( |name| bin_path.join( name ))
+        .find( |path| path.exists() )
+        .context( "none of the declared `exe` names were found under the resolved prefix." )?;
+
+    enforce_version_from_tool( pkg_name, table, tool.to_str().expect( UTF8_PATH ))
+}
+
 fn get_link_name( lib_name: &str ) -> &str {
     let start = if lib_name.starts_with( "lib" ) { 3 } else { 0 };
     match lib_name.rfind('.') {
@@ -309,9 +845,22 @@ fn get_link_name( lib_name: &str ) -> &str {
     }
 }
 
+// Sonames are versioned (`libc.so.6`, `libssl.so.3`), so truncating on the last `.` like
+// `get_link_name` does would keep the version suffix; strip from the first `.so`/`.dylib` instead.
+fn get_soname_link_name( soname: &str ) -> &str {
+    let start = if soname.starts_with( "lib" ) { 3 } else { 0 };
+    let end = [ ".so", ".dylib" ]
+        .iter()
+        .filter_map( |ext| soname.find( ext ))
+        .min()
+        .unwrap_or( soname.len() );
+    &soname[ start..end ]
+}
+
 enum ProbedEx {
     IncDir( String ),
     PcName( String ),
+    Binary( PathBuf ),
 }
 
 impl ProbedEx {
@@ -319,6 +868,7 @@ impl ProbedEx {
         match self {
             ProbedEx::IncDir(_)  => false,
             ProbedEx::PcName(_)  => true,
+            ProbedEx::Binary(_)  => false,
         }
     }
 }
@@ -361,9 +911,6 @@ fn main() {
         return;
     }
 
-    #[cfg( target_os = "freebsd" )]
-    env::set_var( "PKG_CONFIG_ALLOW_CROSS", "1" );
-
     let lib_info_all = LibInfo::new( specs );
 
     let mut downstream_files_for_docs_rs = Vec::<PathBuf>::new();
@@ -373,6 +920,20 @@ fn main() {
             match lib_info_all.probe( pkg_name, false ) {
                 Ok(_) => (),
                 Err( err ) => {
+                    let has_source = lib_info_all.specs.get( pkg_name )
+                        .and_then( |spec| spec.as_table() )
+                        .is_some_and( |table| table.contains_key("source") );
+
+                    if has_source {
+                        match lib_info_all.build_and_probe_from_source( pkg_name ) {
+                            Ok(_) => return,
+                            Err( source_err ) => println!(
+                                "cargo:warning=[clib] failed to build {} from source, error occured: {:?}",
+                                pkg_name, source_err,
+                            ),
+                        }
+                    }
+
                     //if cfg!( target_os = "linux" ) && Path::new( "/.dockerenv" ).exists() {
                         // make docs.rs happy
                         println!( "cargo:warning=[clib] fails to probe library {}, error occured: {:?}", pkg_name, err );